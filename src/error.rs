@@ -4,4 +4,5 @@ pub enum ErrorKind {
     ZeroNotFound,
     CannotExchangeNoneZero,
     CannotExchangeNotNeighbouring,
+    InvalidLayout,
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ use std::default::Default;
 
 use crate::error::ErrorKind;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Direction {
     Top,
     Bottom,
@@ -11,6 +12,18 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    /// 得到相反的方向，用于撤销一次空格移动
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Top => Direction::Bottom,
+            Direction::Bottom => Direction::Top,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
 ///
 /// fifteen puzzle game lib
 /// 数字华容道
@@ -25,13 +38,24 @@ pub enum Direction {
 /// ```
 /// 
 ///   
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct NumHrd {
     /// 华容道的边长
     size: u8,
     /// 0.0 -> 0.1 -> 0.2 -> 1.0 -> 1.1 -> 1.2 -> 2.0 -> ...
     /// 排序顺序
     nums: Vec<Num>,
+    /// 已执行的空格移动历史，用于 `undo`
+    history: Vec<Direction>,
+    /// 被撤销的移动，用于 `redo`
+    redo: Vec<Direction>,
+}
+
+/// 局面相等只看棋盘布局，不看移动历史/redo 栈
+impl PartialEq for NumHrd {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.nums == other.nums
+    }
 }
 
 impl NumHrd {
@@ -50,6 +74,8 @@ impl NumHrd {
         Self {
             size: *s,
             nums,
+            history: Vec::new(),
+            redo: Vec::new(),
         }
     }
 
@@ -78,6 +104,35 @@ impl NumHrd {
         rows
     }
 
+    ///
+    /// 由一个二维数组构造华容道，行数即为边长
+    /// 要求是一个方阵，且恰好包含 0..size*size 的每一个值且不重复，否则返回 InvalidLayout
+    ///
+    pub fn from_2d_vec(rows: Vec<Vec<usize>>) -> Result<NumHrd, ErrorKind> {
+        let size = rows.len();
+        if size == 0 || rows.iter().any(|row| row.len() != size) {
+            return Err(ErrorKind::InvalidLayout);
+        }
+
+        let flat: Vec<usize> = rows.into_iter().flatten().collect();
+        let total = size * size;
+        let mut seen = vec![false; total];
+        for &n in &flat {
+            if n >= total || seen[n] {
+                return Err(ErrorKind::InvalidLayout);
+            }
+            seen[n] = true;
+        }
+
+        let nums = flat.iter().map(Num::new).collect();
+        Ok(Self {
+            size: size as u8,
+            nums,
+            history: Vec::new(),
+            redo: Vec::new(),
+        })
+    }
+
     ///
     /// 交换两个块的位置
     /// 
@@ -160,19 +215,64 @@ impl NumHrd {
         self.nums.iter().position(|x| x.n == *n)
     }
     /// 判断是否成功
-    /// 
+    ///
     pub fn is_win(&self) -> bool {
         self.nums == Self::new(&self.size).nums
     }
 
+    ///
+    /// 判断当前局面是否可解（逆序数奇偶性判定）
+    /// 按行优先展开 nums（忽略空格），统计逆序数；
+    /// 边长为奇数时，局面可解当且仅当逆序数为偶数；
+    /// 边长为偶数时，局面可解当且仅当 (逆序数 + 空格从底部数的行号) 为奇数
+    ///
+    pub fn is_solvable(&self) -> bool {
+        let size = self.size as usize;
+        let flat: Vec<usize> = self.nums.iter().map(|n| n.get_n()).collect();
+        let mut inversions = 0usize;
+        for i in 0..flat.len() {
+            if flat[i] == 0 {
+                continue;
+            }
+            for j in (i + 1)..flat.len() {
+                if flat[j] == 0 {
+                    continue;
+                }
+                if flat[i] > flat[j] {
+                    inversions += 1;
+                }
+            }
+        }
+
+        if size % 2 == 1 {
+            inversions % 2 == 0
+        } else {
+            let blank_index = flat.iter().position(|&n| n == 0).unwrap();
+            let blank_row_from_bottom = size - (blank_index / size);
+            (inversions + blank_row_from_bottom) % 2 == 1
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.size as usize * self.size as usize
     }
 
     ///
-    /// 移动空格所在的位置
+    /// 移动空格所在的位置，并记录到玩家可见的移动历史中
     /// d: direction 空格想移动的方向
     pub fn zero_move(&mut self, d: &Direction) -> Result<bool, ErrorKind> {
+        let zero_index = self.index_by_n(&0).ok_or(ErrorKind::ZeroNotFound)?;
+        let will_move = self.get_dirction_index(&zero_index, d).is_some();
+        self.zero_move_silent(d)?;
+        if will_move {
+            self.push_history(*d);
+        }
+        Ok(true)
+    }
+
+    /// 与 `zero_move` 相同，但不写入 `history`/`redo`
+    /// 供求解器、洗牌等内部算法使用，避免把探索过程中的移动误当作玩家的操作记录下来
+    fn zero_move_silent(&mut self, d: &Direction) -> Result<bool, ErrorKind> {
         let zero_index_opt = self.index_by_n(&0);
         match zero_index_opt {
             Some(zero_index) => {
@@ -224,13 +324,81 @@ impl NumHrd {
         }
     }
 
+    /// 得到从 from 到 to 所对应的方向，两者不相邻时返回 None
+    fn direction_from_to(&self, from: &usize, to: &usize) -> Option<Direction> {
+        for d in [Direction::Top, Direction::Bottom, Direction::Left, Direction::Right] {
+            if self.get_dirction_index(from, &d) == Some(*to) {
+                return Some(d);
+            }
+        }
+        None
+    }
+
+    /// 记录一次成功的空格移动，并清空 redo 历史
+    fn push_history(&mut self, d: Direction) {
+        self.history.push(d);
+        self.redo.clear();
+    }
+
+    ///
+    /// 撤销上一次移动
+    /// 返回 false 表示没有可以撤销的历史
+    ///
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(d) => {
+                let zero_index = self.index_by_n(&0).unwrap();
+                if let Some(prev_index) = self.get_dirction_index(&zero_index, &d.opposite()) {
+                    self.exchange(&zero_index, &prev_index).unwrap();
+                }
+                self.redo.push(d);
+                true
+            },
+            None => false,
+        }
+    }
+
+    ///
+    /// 重做上一次被撤销的移动
+    /// 返回 false 表示没有可以重做的历史
+    ///
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(d) => {
+                let zero_index = self.index_by_n(&0).unwrap();
+                if let Some(other_index) = self.get_dirction_index(&zero_index, &d) {
+                    self.exchange(&zero_index, &other_index).unwrap();
+                }
+                self.history.push(d);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// 已执行的移动历史
+    pub fn history(&self) -> &[Direction] {
+        &self.history
+    }
+
+    /// 已执行的移动次数
+    pub fn move_count(&self) -> usize {
+        self.history.len()
+    }
+
     ///
     /// 移动指定索引的块
-    /// 
+    ///
     pub fn move_num(&mut self, index: usize) -> bool {
         if let Some(zero_index) = self.index_by_n(&0) {
+            let dir = self.direction_from_to(&zero_index, &index);
             return match self.exchange(&index, &zero_index) {
-                Ok(_) => true,
+                Ok(_) => {
+                    if let Some(d) = dir {
+                        self.push_history(d);
+                    }
+                    true
+                },
                 Err(_) => false,
             }
         }
@@ -241,6 +409,260 @@ impl NumHrd {
         let index = self.index_by_point(point);
         self.move_num(index)
     }
+
+    ///
+    /// 打乱当前局面，生成一个保证可解的随机起始局面
+    /// steps: 随机执行的合法空格移动次数
+    /// seed: 随机种子，相同的 seed 和 steps 得到相同的打乱结果
+    ///
+    /// 每一步都会从当前合法的方向中随机挑选一个（不会立即撤销上一步移动），
+    /// 由于每次移动都保持可解性，打乱后的局面必然可解
+    ///
+    pub fn shuffle(&mut self, steps: usize, seed: u64) {
+        let mut rng = SplitMix64::new(seed);
+        let mut last: Option<Direction> = None;
+        for _ in 0..steps {
+            let zero_index = self.index_by_n(&0).unwrap();
+            let candidates: Vec<Direction> = [Direction::Top, Direction::Bottom, Direction::Left, Direction::Right]
+                .into_iter()
+                .filter(|d| !last.map_or(false, |l| *d == l.opposite()))
+                .filter(|d| self.get_dirction_index(&zero_index, d).is_some())
+                .collect();
+            if candidates.is_empty() {
+                break;
+            }
+            let pick = candidates[rng.next_bound(candidates.len())];
+            self.zero_move_silent(&pick).unwrap();
+            last = Some(pick);
+        }
+    }
+
+    ///
+    /// 求解华容道，返回一条可以到达胜利局面的最短路径（空格的移动方向序列）
+    /// 不可解的局面返回 None
+    ///
+    /// 使用 IDA*（迭代加深 A*）算法，启发函数为曼哈顿距离之和
+    ///
+    pub fn solve(&self) -> Option<Vec<Direction>> {
+        if self.is_win() {
+            return Some(Vec::new());
+        }
+        if !self.is_solvable() {
+            return None;
+        }
+
+        let mut board = self.clone();
+        let mut threshold = board.heuristic();
+        let mut path: Vec<Direction> = Vec::new();
+        loop {
+            match board.ida_search(0, threshold, &mut path, None) {
+                None => return Some(path),
+                Some(next) => {
+                    if next == u32::MAX {
+                        return None;
+                    }
+                    threshold = next;
+                }
+            }
+        }
+    }
+
+    /// IDA* 的深度优先搜索部分
+    /// 返回 None 表示已经找到解（路径记录在 path 中）
+    /// 返回 Some(next_threshold) 表示本次未找到解，记录下一次迭代应使用的阈值
+    fn ida_search(
+        &mut self,
+        g: u32,
+        threshold: u32,
+        path: &mut Vec<Direction>,
+        last: Option<Direction>,
+    ) -> Option<u32> {
+        let f = g + self.heuristic();
+        if f > threshold {
+            return Some(f);
+        }
+        if self.is_win() {
+            return None;
+        }
+
+        let zero_index = self.index_by_n(&0).unwrap();
+        let mut min_next = u32::MAX;
+        for d in [Direction::Top, Direction::Bottom, Direction::Left, Direction::Right] {
+            if last.map_or(false, |l| d == l.opposite()) {
+                continue;
+            }
+            if self.get_dirction_index(&zero_index, &d).is_none() {
+                continue;
+            }
+            self.zero_move_silent(&d).unwrap();
+            path.push(d);
+            match self.ida_search(g + 1, threshold, path, Some(d)) {
+                None => return None,
+                Some(next) => min_next = min_next.min(next),
+            }
+            path.pop();
+            self.zero_move_silent(&d.opposite()).unwrap();
+        }
+        Some(min_next)
+    }
+
+    /// 启发函数：所有非空格块到其目标位置的曼哈顿距离之和
+    fn heuristic(&self) -> u32 {
+        let size = self.size as usize;
+        let mut h = 0u32;
+        for (index, num) in self.nums.iter().enumerate() {
+            let v = num.get_n();
+            if v == 0 {
+                continue;
+            }
+            let (goal_r, goal_c) = ((v - 1) / size, (v - 1) % size);
+            let (r, c) = (index / size, index % size);
+            h += (r as i64 - goal_r as i64).unsigned_abs() as u32;
+            h += (c as i64 - goal_c as i64).unsigned_abs() as u32;
+        }
+        h
+    }
+
+    ///
+    /// 在给定的时间预算内，使用模拟退火求一个可行（不一定最短）的解
+    /// millis: 求解的最长耗时（毫秒）
+    /// seed: 随机种子，用于邻居选择和接受概率的随机数抽取
+    ///
+    /// 以曼哈顿距离之和作为能量 E，邻居状态为一次合法的空格移动；
+    /// 能量降低的移动总是接受，能量升高的移动以 exp(-ΔE / T) 的概率接受；
+    /// 温度 T 从初始温度 (E + 1) * 10 开始，随耗时占预算的比例几何降低，预算用尽时降到初始温度的 0.001 倍；
+    /// 因此预算内 T 始终高于 min_temperature (0.01)，“温度不高于 min_temperature 时从历史最优状态重新出发”
+    /// 的分支不会被触发，搜索过程不会回到历史最优状态。注意：降温曲线由实际耗时（wall clock）驱动，
+    /// 相同的 (millis, seed) 在不同机器或不同负载下走过的轨迹不保证完全一致
+    ///
+    /// 返回 `Ok(path)` 表示 `path` 能让局面到达终局；
+    /// 预算耗尽仍未到达终局时返回 `Err(path)`，`path` 是搜索过程中找到的最优（能量最低）但未必可行的路径
+    ///
+    pub fn solve_annealing(&self, millis: u64, seed: u64) -> Result<Vec<Direction>, Vec<Direction>> {
+        let start = std::time::Instant::now();
+        let budget_ms = millis.max(1) as f64;
+        let mut rng = SplitMix64::new(seed);
+
+        let mut current = self.clone();
+        let mut current_energy = current.heuristic() as f64;
+        let mut path: Vec<Direction> = Vec::new();
+        let mut last: Option<Direction> = None;
+
+        let mut best = current.clone();
+        let mut best_energy = current_energy;
+        let mut best_path = path.clone();
+
+        let initial_temperature = (current_energy + 1.0) * 10.0;
+        let min_temperature = 0.01;
+
+        while current_energy > 0.0 {
+            let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+            if elapsed_ms >= budget_ms {
+                break;
+            }
+            let elapsed_fraction = (elapsed_ms / budget_ms).min(1.0);
+            let temperature = (initial_temperature * 0.001f64.powf(elapsed_fraction)).max(min_temperature);
+
+            let zero_index = current.index_by_n(&0).unwrap();
+            let candidates: Vec<Direction> = [Direction::Top, Direction::Bottom, Direction::Left, Direction::Right]
+                .into_iter()
+                .filter(|d| !last.map_or(false, |l| *d == l.opposite()))
+                .filter(|d| current.get_dirction_index(&zero_index, d).is_some())
+                .collect();
+            if candidates.is_empty() {
+                break;
+            }
+            let pick = candidates[rng.next_bound(candidates.len())];
+            current.zero_move_silent(&pick).unwrap();
+            let next_energy = current.heuristic() as f64;
+            let delta = next_energy - current_energy;
+
+            let accept = delta <= 0.0 || {
+                let r = (rng.next_u64() as f64) / (u64::MAX as f64);
+                r < (-delta / temperature).exp()
+            };
+
+            if accept {
+                path.push(pick);
+                last = Some(pick);
+                current_energy = next_energy;
+                if current_energy < best_energy {
+                    best_energy = current_energy;
+                    best = current.clone();
+                    best_path = path.clone();
+                }
+            } else {
+                current.zero_move_silent(&pick.opposite()).unwrap();
+            }
+
+            if temperature <= min_temperature {
+                current = best.clone();
+                current_energy = best_energy;
+                path = best_path.clone();
+                last = path.last().copied();
+            }
+        }
+
+        if best_energy == 0.0 {
+            Ok(best_path)
+        } else {
+            Err(best_path)
+        }
+    }
+}
+
+/// 输出为按行输出的文本形式，行内数字以空格分隔，行间以换行分隔
+impl std::fmt::Display for NumHrd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<String> = self
+            .as_2d_vec()
+            .iter()
+            .map(|row| row.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" "))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// 解析由 `Display` 输出的文本形式，复用 `from_2d_vec` 的校验逻辑
+impl std::str::FromStr for NumHrd {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<Vec<usize>> = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|tok| tok.parse::<usize>().map_err(|_| ErrorKind::InvalidLayout))
+                    .collect::<Result<Vec<usize>, ErrorKind>>()
+            })
+            .collect::<Result<Vec<Vec<usize>>, ErrorKind>>()?;
+        Self::from_2d_vec(rows)
+    }
+}
+
+/// 一个内嵌的 SplitMix64 伪随机数生成器，供 `shuffle` 和 `solve_annealing` 使用，避免引入外部随机数依赖
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// 返回 `[0, bound)` 区间内的随机数
+    fn next_bound(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
 }
 
 /// 表示一个数字块
@@ -401,5 +823,185 @@ mod tests {
                 n: 5,
             }));
         }
+
+        #[test]
+        fn solve_already_won_works() {
+            let numhrd = NumHrd::new(&3);
+            assert_eq!(numhrd.solve(), Some(Vec::new()));
+        }
+
+        #[test]
+        fn solve_works() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.zero_move(&Direction::Left).unwrap();
+            numhrd.zero_move(&Direction::Top).unwrap();
+
+            let path = numhrd.solve().unwrap();
+            for d in &path {
+                numhrd.zero_move(d).unwrap();
+            }
+            assert!(numhrd.is_win());
+        }
+
+        #[test]
+        fn solve_unsolvable_works() {
+            let mut numhrd = NumHrd::new(&3);
+            // swap two non-zero tiles to produce an odd permutation, which is unsolvable
+            numhrd.nums.swap(0, 1);
+            assert_eq!(numhrd.solve(), None);
+        }
+
+        #[test]
+        fn is_solvable_works() {
+            let numhrd = NumHrd::new(&3);
+            assert_eq!(numhrd.is_solvable(), true);
+
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.nums.swap(0, 1);
+            assert_eq!(numhrd.is_solvable(), false);
+
+            let numhrd = NumHrd::new(&4);
+            assert_eq!(numhrd.is_solvable(), true);
+
+            let mut numhrd = NumHrd::new(&4);
+            numhrd.nums.swap(0, 1);
+            assert_eq!(numhrd.is_solvable(), false);
+        }
+
+        #[test]
+        fn shuffle_works() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.shuffle(50, 42);
+            assert_eq!(numhrd.is_solvable(), true);
+            assert_ne!(numhrd, NumHrd::new(&3));
+        }
+
+        #[test]
+        fn shuffle_is_reproducible() {
+            let mut a = NumHrd::new(&4);
+            a.shuffle(30, 7);
+            let mut b = NumHrd::new(&4);
+            b.shuffle(30, 7);
+            assert_eq!(a, b);
+        }
+
+        #[test]
+        fn shuffle_does_not_pollute_player_history() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.shuffle(50, 42);
+            assert_eq!(numhrd.move_count(), 0);
+            assert!(numhrd.history().is_empty());
+            assert!(!numhrd.undo());
+        }
+
+        #[test]
+        fn solve_annealing_already_won_works() {
+            let numhrd = NumHrd::new(&3);
+            assert_eq!(numhrd.solve_annealing(100, 1), Ok(Vec::new()));
+        }
+
+        #[test]
+        fn solve_annealing_works() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.shuffle(20, 99);
+            let path = numhrd.solve_annealing(5000, 99).unwrap();
+
+            let mut replay = numhrd.clone();
+            for d in &path {
+                replay.zero_move(d).unwrap();
+            }
+            assert!(replay.is_win());
+        }
+
+        #[test]
+        fn undo_redo_works() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.zero_move(&Direction::Left).unwrap();
+            numhrd.zero_move(&Direction::Top).unwrap();
+            assert_eq!(numhrd.move_count(), 2);
+            assert_eq!(numhrd.history(), &[Direction::Left, Direction::Top]);
+
+            let after_moves = numhrd.clone();
+
+            assert!(numhrd.undo());
+            assert!(numhrd.undo());
+            assert_eq!(numhrd.move_count(), 0);
+            assert_eq!(numhrd, NumHrd::new(&3));
+
+            assert!(!numhrd.undo());
+
+            assert!(numhrd.redo());
+            assert!(numhrd.redo());
+            assert_eq!(numhrd, after_moves);
+
+            assert!(!numhrd.redo());
+        }
+
+        #[test]
+        fn new_move_clears_redo_stack() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.zero_move(&Direction::Left).unwrap();
+            numhrd.undo();
+            assert_eq!(numhrd.move_count(), 0);
+
+            numhrd.zero_move(&Direction::Top).unwrap();
+            assert!(!numhrd.redo());
+        }
+
+        #[test]
+        fn from_2d_vec_works() {
+            let rows = vec![
+                vec![1, 2, 3],
+                vec![4, 5, 6],
+                vec![7, 8, 0],
+            ];
+            let numhrd = NumHrd::from_2d_vec(rows).unwrap();
+            assert_eq!(numhrd, NumHrd::new(&3));
+        }
+
+        #[test]
+        fn from_2d_vec_rejects_non_square() {
+            let rows = vec![
+                vec![1, 2, 3],
+                vec![4, 5, 6],
+            ];
+            assert_eq!(NumHrd::from_2d_vec(rows), Err(ErrorKind::InvalidLayout));
+        }
+
+        #[test]
+        fn from_2d_vec_rejects_duplicate_or_out_of_range() {
+            let rows = vec![
+                vec![1, 2, 3],
+                vec![4, 5, 6],
+                vec![7, 8, 8],
+            ];
+            assert_eq!(NumHrd::from_2d_vec(rows), Err(ErrorKind::InvalidLayout));
+
+            let rows = vec![
+                vec![1, 2, 3],
+                vec![4, 5, 6],
+                vec![7, 8, 9],
+            ];
+            assert_eq!(NumHrd::from_2d_vec(rows), Err(ErrorKind::InvalidLayout));
+        }
+
+        #[test]
+        fn to_string_and_from_str_round_trip() {
+            let mut numhrd = NumHrd::new(&3);
+            numhrd.zero_move(&Direction::Left).unwrap();
+            numhrd.zero_move(&Direction::Top).unwrap();
+
+            let text = numhrd.to_string();
+            assert_eq!(text, "1 2 3\n4 0 6\n7 5 8".to_string());
+
+            let parsed: NumHrd = text.parse().unwrap();
+            assert_eq!(parsed, numhrd);
+        }
+
+        #[test]
+        fn from_str_rejects_invalid_layout() {
+            let result: Result<NumHrd, ErrorKind> = "1 2\n3 3".parse();
+            assert_eq!(result, Err(ErrorKind::InvalidLayout));
+        }
     }
 }
\ No newline at end of file